@@ -3,11 +3,20 @@ use gamma::ToneCurve;
 use internal::quick_saturate_word;
 use pcs::{lab_to_xyz, xyz_to_lab, MAX_ENCODEABLE_XYZ};
 use std::fmt;
+use std::sync::Arc;
 use transform::NamedColorList;
 use {CIELab, CIEXYZ};
 
 type StageEvalFn = fn(&[f32], &mut [f32], &Stage);
 
+/// Evaluates a stage over a contiguous block of `pixel_count` pixels at once.
+///
+/// `input` and `output` are interleaved (pixel-major) with the stage’s input
+/// and output channel counts as stride. Processing a strip of pixels per stage,
+/// rather than re-walking the stage list per pixel, lets the compiler
+/// auto-vectorize the inner matrix and curve loops.
+type StageEvalBatchFn = fn(&[f32], &mut [f32], usize, &Stage);
+
 /// Multi process elements types
 #[repr(u32)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -51,9 +60,14 @@ pub enum StageType {
     FloatPCS2XYZ = 0x78326420,
     /// `clp `
     ClipNegatives = 0x636c7020,
+    /// `cust`
+    Custom = 0x63757374,
 }
 
-#[derive(Debug, Clone)]
+/// Evaluator for a user-defined custom stage.
+pub type CustomStageFn = dyn Fn(&[f32], &mut [f32]) + Send + Sync;
+
+#[derive(Clone)]
 pub(crate) enum StageData {
     None,
     Matrix {
@@ -62,6 +76,43 @@ pub(crate) enum StageData {
     },
     Curves(Vec<ToneCurve>),
     NamedColorList(NamedColorList),
+    CLut {
+        grid_points: Vec<u32>,
+        table: Vec<f32>,
+        input_channels: u32,
+        output_channels: u32,
+    },
+    Custom(Arc<CustomStageFn>),
+}
+
+impl fmt::Debug for StageData {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StageData::None => write!(f, "None"),
+            StageData::Matrix { matrix, offset } => f
+                .debug_struct("Matrix")
+                .field("matrix", matrix)
+                .field("offset", offset)
+                .finish(),
+            StageData::Curves(curves) => f.debug_tuple("Curves").field(curves).finish(),
+            StageData::NamedColorList(list) => {
+                f.debug_tuple("NamedColorList").field(list).finish()
+            }
+            StageData::CLut {
+                grid_points,
+                table,
+                input_channels,
+                output_channels,
+            } => f
+                .debug_struct("CLut")
+                .field("grid_points", grid_points)
+                .field("table", table)
+                .field("input_channels", input_channels)
+                .field("output_channels", output_channels)
+                .finish(),
+            StageData::Custom(_) => write!(f, "Custom(..)"),
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -172,6 +223,76 @@ impl Stage {
         stage
     }
 
+    /// Creates a user-defined stage evaluated by the supplied closure.
+    ///
+    /// This lets library users plug in their own transform step (for instance a
+    /// custom gamut-mapping or soft-proofing stage) without modifying the crate.
+    /// The closure receives the `in_ch` input channels and must fill `out_ch`
+    /// output channels, with all values in the 0..1.0 domain.
+    pub fn new_custom<F>(in_ch: u32, out_ch: u32, f: F) -> Stage
+    where
+        F: Fn(&[f32], &mut [f32]) + Send + Sync + 'static,
+    {
+        Self::alloc(
+            StageType::Custom,
+            in_ch,
+            out_ch,
+            evaluate_custom,
+            StageData::Custom(Arc::new(f)),
+        )
+    }
+
+    /// Creates a named-color stage for spot/Pantone-style transforms.
+    ///
+    /// The single input channel is treated as a scaled index into `list`. When
+    /// `use_pcs` is set the stage outputs the stored PCS coordinates (three
+    /// channels), otherwise it outputs the device colorant values. All outputs
+    /// are normalized to the 0..1.0 range like the other stages.
+    pub(crate) fn new_named_color(list: NamedColorList, use_pcs: bool) -> Stage {
+        let out_ch = if use_pcs {
+            3
+        } else {
+            list.colorant_count() as u32
+        };
+
+        // The PCS/device choice is baked into the evaluator, as in the
+        // reference implementation.
+        let eval_fn = if use_pcs {
+            evaluate_named_color_pcs
+        } else {
+            evaluate_named_color_device
+        };
+
+        Self::alloc(
+            StageType::NamedColor,
+            1,
+            out_ch,
+            eval_fn,
+            StageData::NamedColorList(list),
+        )
+    }
+
+    /// Creates a multidimensional lookup table stage.
+    ///
+    /// `grid` holds the number of sample points along each input dimension and
+    /// `table` the interleaved output values (output channels varying fastest,
+    /// first input dimension varying slowest). All values stay in the 0..1.0
+    /// domain.
+    pub(crate) fn new_clut(grid: &[u32], in_ch: u32, out_ch: u32, table: &[f32]) -> Stage {
+        Self::alloc(
+            StageType::CLut,
+            in_ch,
+            out_ch,
+            evaluate_clut,
+            StageData::CLut {
+                grid_points: grid.to_vec(),
+                table: table.to_vec(),
+                input_channels: in_ch,
+                output_channels: out_ch,
+            },
+        )
+    }
+
     pub(crate) fn new_xyz_to_lab() -> Stage {
         Self::alloc(
             StageType::XYZ2Lab,
@@ -273,6 +394,21 @@ impl Stage {
         stage.implements = StageType::Identity;
         stage
     }
+
+    /// Returns the batched evaluator for this stage.
+    ///
+    /// Stages with a vectorizable inner loop provide a dedicated block version;
+    /// anything else falls back to invoking the scalar evaluator per pixel.
+    fn batch_eval_fn(&self) -> StageEvalBatchFn {
+        match self.ty {
+            StageType::Matrix => evaluate_matrix_batch,
+            StageType::CurveSet => evaluate_curves_batch,
+            StageType::ClipNegatives => clipper_batch,
+            StageType::XYZ2Lab => evaluate_xyz_to_lab_batch,
+            StageType::Lab2XYZ => evaluate_lab_to_xyz_batch,
+            _ => eval_batch_scalar,
+        }
+    }
 }
 
 impl fmt::Debug for Stage {
@@ -346,6 +482,137 @@ impl Pipeline {
         (self.eval_float_fn)(input, output, self);
     }
 
+    /// Evaluates the pipeline over many pixels at once.
+    ///
+    /// `input` and `output` are interleaved buffers holding `pixel_count` pixels
+    /// with `input_channels`/`output_channels` as stride. Each stage runs over a
+    /// block of pixels before moving on to the next, the way a raster-pipeline
+    /// engine processes a strip of pixels per stage. The scalar [`eval_float`]
+    /// remains available unchanged.
+    ///
+    /// [`eval_float`]: Pipeline::eval_float
+    pub fn eval_float_batch(&self, input: &[f32], output: &mut [f32], pixel_count: usize) {
+        const LANES: usize = 16;
+
+        let in_ch = self.input_channels as usize;
+        let out_ch = self.output_channels as usize;
+
+        let mut storage = [
+            vec![0.; LANES * MAX_STAGE_CHANNELS],
+            vec![0.; LANES * MAX_STAGE_CHANNELS],
+        ];
+
+        let mut done = 0;
+        while done < pixel_count {
+            let block = (pixel_count - done).min(LANES);
+
+            // Load this block into phase 0 with the first stage’s stride.
+            let mut phase = 0;
+            for p in 0..block {
+                let src = &input[(done + p) * in_ch..][..in_ch];
+                storage[phase][p * in_ch..][..in_ch].copy_from_slice(src);
+            }
+
+            let mut cur_ch = in_ch;
+            for stage in &self.elements {
+                let next_phase = phase ^ 1;
+                let out_stage_ch = stage.output_channels as usize;
+                let (src, dst) = if phase == 0 {
+                    let (a, b) = storage.split_at_mut(1);
+                    (&a[0], &mut b[0])
+                } else {
+                    let (a, b) = storage.split_at_mut(1);
+                    (&b[0], &mut a[0])
+                };
+                (stage.batch_eval_fn())(
+                    &src[..block * cur_ch],
+                    &mut dst[..block * out_stage_ch],
+                    block,
+                    stage,
+                );
+                phase = next_phase;
+                cur_ch = out_stage_ch;
+            }
+
+            // Store the result block.
+            for p in 0..block {
+                let dst = &mut output[(done + p) * out_ch..][..out_ch];
+                dst.copy_from_slice(&storage[phase][p * out_ch..][..out_ch]);
+            }
+
+            done += block;
+        }
+    }
+
+    /// Rewrites the element chain into an equivalent but cheaper one.
+    ///
+    /// This mirrors the matrix-shaper collapsing the reference optimization code
+    /// performs: identity stages are dropped, consecutive matrices are
+    /// multiplied into one (a matrix immediately followed by its inverse thus
+    /// collapses to nothing), and consecutive curve sets are composed
+    /// channel-by-channel. It is opt-in, so a pipeline kept for ICC
+    /// serialization still round-trips its stages exactly.
+    pub fn optimize(&mut self) {
+        let mut out: Vec<Stage> = Vec::new();
+
+        for stage in self.elements.drain(..) {
+            // Drop identities and identity curve stages.
+            if stage.implements == StageType::Identity || stage.ty == StageType::Identity {
+                continue;
+            }
+
+            if let Some(last) = out.last() {
+                // Multiply two consecutive matrices into a single one.
+                if last.ty == StageType::Matrix
+                    && stage.ty == StageType::Matrix
+                    && last.output_channels == stage.input_channels
+                {
+                    let merged = compose_matrices(last, &stage);
+                    out.pop();
+                    // A matrix fused with its inverse collapses to nothing.
+                    if !is_identity_matrix(&merged) {
+                        out.push(merged);
+                    }
+                    continue;
+                }
+
+                // Merge consecutive curve sets by composing their tone curves.
+                if last.ty == StageType::CurveSet
+                    && stage.ty == StageType::CurveSet
+                    && last.output_channels == stage.input_channels
+                {
+                    let merged = compose_curves(last, &stage);
+                    out.pop();
+                    out.push(merged);
+                    continue;
+                }
+            }
+
+            out.push(stage);
+        }
+
+        self.elements = out;
+        self.bless();
+    }
+
+    /// Produces a pipeline computing the inverse transform, as the reference
+    /// `cmsPipelineReverse` does.
+    ///
+    /// Each stage is replaced, in reverse order, by its analytic inverse:
+    /// matrices invert via Gauss-Jordan (offset handled as `O' = -M^-1 * O`),
+    /// curve sets invert each tone curve channel-wise, the Lab/XYZ and
+    /// normalize stages swap to their counterparts, and identities stay
+    /// identities. Returns `None` when a stage (for instance a non-invertible
+    /// CLUT) cannot be reversed, so callers can fall back to sampling-based
+    /// inversion.
+    pub fn reverse(&self) -> Option<Pipeline> {
+        let mut result = Pipeline::alloc(self.output_channels, self.input_channels);
+        for stage in self.elements.iter().rev() {
+            result.append_stage(reverse_stage(stage)?);
+        }
+        Some(result)
+    }
+
     pub(crate) fn prepend_stage(&mut self, stage: Stage) {
         self.elements.insert(0, stage);
         self.bless();
@@ -479,6 +746,424 @@ fn evaluate_curves(input: &[f32], output: &mut [f32], stage: &Stage) {
     }
 }
 
+/// Evaluates a multidimensional lookup table.
+///
+/// The common three-input case uses tetrahedral interpolation; any other
+/// dimensionality falls back to general n-linear interpolation over the `2^N`
+/// surrounding grid corners.
+fn evaluate_clut(input: &[f32], output: &mut [f32], stage: &Stage) {
+    let (grid_points, table, in_ch, out_ch) = match stage.data {
+        StageData::CLut {
+            ref grid_points,
+            ref table,
+            input_channels,
+            output_channels,
+        } => (
+            grid_points,
+            table,
+            input_channels as usize,
+            output_channels as usize,
+        ),
+        _ => panic!("Invalid stage data (this shouldn’t happen)"),
+    };
+
+    // Row-major strides, output channels varying fastest.
+    let mut strides = vec![0usize; in_ch];
+    strides[in_ch - 1] = out_ch;
+    for d in (0..in_ch - 1).rev() {
+        strides[d] = strides[d + 1] * grid_points[d + 1] as usize;
+    }
+
+    if in_ch == 3 {
+        let n0 = grid_points[0] as usize;
+        let n1 = grid_points[1] as usize;
+        let n2 = grid_points[2] as usize;
+
+        let fx = input[0].max(0.).min(1.) * (n0 - 1) as f32;
+        let fy = input[1].max(0.).min(1.) * (n1 - 1) as f32;
+        let fz = input[2].max(0.).min(1.) * (n2 - 1) as f32;
+
+        let x0 = fx.floor() as usize;
+        let y0 = fy.floor() as usize;
+        let z0 = fz.floor() as usize;
+        let rx = fx - x0 as f32;
+        let ry = fy - y0 as f32;
+        let rz = fz - z0 as f32;
+
+        // Corner fetch, clamped at the top edge of each axis.
+        let corner = |dx: usize, dy: usize, dz: usize, c: usize| -> f32 {
+            let xi = (x0 + dx).min(n0 - 1);
+            let yi = (y0 + dy).min(n1 - 1);
+            let zi = (z0 + dz).min(n2 - 1);
+            table[xi * strides[0] + yi * strides[1] + zi * strides[2] + c]
+        };
+
+        for c in 0..out_ch {
+            let c0 = corner(0, 0, 0, c);
+            // Pick the tetrahedron from the ordering of the fractional parts.
+            let (c1, c2, c3) = if rx >= ry && ry >= rz {
+                (
+                    corner(1, 0, 0, c) - c0,
+                    corner(1, 1, 0, c) - corner(1, 0, 0, c),
+                    corner(1, 1, 1, c) - corner(1, 1, 0, c),
+                )
+            } else if rx >= rz && rz >= ry {
+                (
+                    corner(1, 0, 0, c) - c0,
+                    corner(1, 1, 1, c) - corner(1, 0, 1, c),
+                    corner(1, 0, 1, c) - corner(1, 0, 0, c),
+                )
+            } else if rz >= rx && rx >= ry {
+                (
+                    corner(1, 0, 1, c) - corner(0, 0, 1, c),
+                    corner(1, 1, 1, c) - corner(1, 0, 1, c),
+                    corner(0, 0, 1, c) - c0,
+                )
+            } else if ry >= rx && rx >= rz {
+                (
+                    corner(1, 1, 0, c) - corner(0, 1, 0, c),
+                    corner(0, 1, 0, c) - c0,
+                    corner(1, 1, 1, c) - corner(1, 1, 0, c),
+                )
+            } else if ry >= rz && rz >= rx {
+                (
+                    corner(1, 1, 1, c) - corner(0, 1, 1, c),
+                    corner(0, 1, 0, c) - c0,
+                    corner(0, 1, 1, c) - corner(0, 1, 0, c),
+                )
+            } else {
+                (
+                    corner(1, 1, 1, c) - corner(0, 1, 1, c),
+                    corner(0, 1, 1, c) - corner(0, 0, 1, c),
+                    corner(0, 0, 1, c) - c0,
+                )
+            };
+
+            output[c] = c0 + rx * c1 + ry * c2 + rz * c3;
+        }
+    } else {
+        // General n-linear interpolation over the 2^N surrounding corners.
+        let mut x0 = vec![0usize; in_ch];
+        let mut rx = vec![0f32; in_ch];
+        for d in 0..in_ch {
+            let gp = grid_points[d] as usize;
+            let f = input[d].max(0.).min(1.) * (gp - 1) as f32;
+            x0[d] = f.floor() as usize;
+            rx[d] = f - x0[d] as f32;
+        }
+
+        for c in 0..out_ch {
+            output[c] = 0.;
+        }
+
+        for corner in 0..(1usize << in_ch) {
+            let mut weight = 1f32;
+            let mut index = 0usize;
+            for d in 0..in_ch {
+                let gp = grid_points[d] as usize;
+                if (corner >> d) & 1 == 1 {
+                    weight *= rx[d];
+                    index += (x0[d] + 1).min(gp - 1) * strides[d];
+                } else {
+                    weight *= 1. - rx[d];
+                    index += x0[d].min(gp - 1) * strides[d];
+                }
+            }
+            if weight == 0. {
+                continue;
+            }
+            for c in 0..out_ch {
+                output[c] += weight * table[index + c];
+            }
+        }
+    }
+}
+
+/// Number of samples used when composing tone curves into a tabulated one.
+const CURVE_COMPOSE_SAMPLES: usize = 4096;
+
+/// Multiplies two consecutive matrix stages `first` then `second` into a single
+/// matrix stage. Matrices are stored row-major as `matrix[i * in_ch + j]`; the
+/// result is `M = M2 * M1` with offset `O = M2 * O1 + O2`.
+fn compose_matrices(first: &Stage, second: &Stage) -> Stage {
+    let (a, a_off) = match first.data {
+        StageData::Matrix {
+            ref matrix,
+            ref offset,
+        } => (matrix, offset),
+        _ => panic!("Invalid stage data (this shouldn’t happen)"),
+    };
+    let (b, b_off) = match second.data {
+        StageData::Matrix {
+            ref matrix,
+            ref offset,
+        } => (matrix, offset),
+        _ => panic!("Invalid stage data (this shouldn’t happen)"),
+    };
+
+    let n_in = first.input_channels as usize; // columns of A and of the result
+    let n_mid = first.output_channels as usize; // rows of A == columns of B
+    let n_out = second.output_channels as usize; // rows of B and of the result
+
+    let mut m = vec![0f64; n_out * n_in];
+    for i in 0..n_out {
+        for k in 0..n_in {
+            let mut acc = 0.;
+            for j in 0..n_mid {
+                acc += b[i * n_mid + j] * a[j * n_in + k];
+            }
+            m[i * n_in + k] = acc;
+        }
+    }
+
+    // O = M2 * O1 + O2
+    let offset = if a_off.is_some() || b_off.is_some() {
+        let mut o = vec![0f64; n_out];
+        for i in 0..n_out {
+            let mut acc = 0.;
+            if let Some(a_off) = a_off {
+                for j in 0..n_mid {
+                    acc += b[i * n_mid + j] * a_off[j];
+                }
+            }
+            if let Some(b_off) = b_off {
+                acc += b_off[i];
+            }
+            o[i] = acc;
+        }
+        Some(o)
+    } else {
+        None
+    };
+
+    Stage::new_matrix(n_out as u32, n_in as u32, &m, offset.as_deref())
+}
+
+/// Returns true when a matrix stage is the identity (square, unit diagonal, no
+/// or zero offset).
+fn is_identity_matrix(stage: &Stage) -> bool {
+    const EPSILON: f64 = 1e-9;
+
+    if stage.input_channels != stage.output_channels {
+        return false;
+    }
+    let (matrix, offset) = match stage.data {
+        StageData::Matrix {
+            ref matrix,
+            ref offset,
+        } => (matrix, offset),
+        _ => return false,
+    };
+
+    let n = stage.input_channels as usize;
+    for i in 0..n {
+        for j in 0..n {
+            let expected = if i == j { 1. } else { 0. };
+            if (matrix[i * n + j] - expected).abs() > EPSILON {
+                return false;
+            }
+        }
+    }
+    if let Some(offset) = offset {
+        if offset.iter().any(|&o| o.abs() > EPSILON) {
+            return false;
+        }
+    }
+    true
+}
+
+/// Composes two consecutive curve-set stages into one, building each output
+/// curve by sampling `second(first(x))` channel-by-channel.
+fn compose_curves(first: &Stage, second: &Stage) -> Stage {
+    let a = match first.data {
+        StageData::Curves(ref c) => c,
+        _ => panic!("Invalid stage data (this shouldn’t happen)"),
+    };
+    let b = match second.data {
+        StageData::Curves(ref c) => c,
+        _ => panic!("Invalid stage data (this shouldn’t happen)"),
+    };
+
+    let channels = a.len();
+    let mut curves = Vec::with_capacity(channels);
+    for ch in 0..channels {
+        let mut samples = vec![0f32; CURVE_COMPOSE_SAMPLES];
+        for (i, sample) in samples.iter_mut().enumerate() {
+            let x = i as f32 / (CURVE_COMPOSE_SAMPLES - 1) as f32;
+            *sample = b[ch].eval_float(a[ch].eval_float(x));
+        }
+        curves.push(ToneCurve::new_tabulated(&samples).unwrap());
+    }
+
+    Stage::new_tone_curves(channels as u32, Some(&curves))
+}
+
+/// Inverts an `n`×`n` matrix by Gauss-Jordan elimination with partial pivoting.
+/// Returns `None` when the matrix is singular.
+fn invert_matrix(m: &[f64], n: usize) -> Option<Vec<f64>> {
+    const EPSILON: f64 = 1e-12;
+    let w = 2 * n;
+    let mut a = vec![0f64; n * w];
+    for i in 0..n {
+        for j in 0..n {
+            a[i * w + j] = m[i * n + j];
+        }
+        a[i * w + n + i] = 1.;
+    }
+
+    for col in 0..n {
+        // Partial pivot.
+        let mut pivot = col;
+        let mut max = a[col * w + col].abs();
+        for r in (col + 1)..n {
+            let v = a[r * w + col].abs();
+            if v > max {
+                max = v;
+                pivot = r;
+            }
+        }
+        if a[pivot * w + col].abs() < EPSILON {
+            return None;
+        }
+        if pivot != col {
+            for j in 0..w {
+                a.swap(col * w + j, pivot * w + j);
+            }
+        }
+
+        let d = a[col * w + col];
+        for j in 0..w {
+            a[col * w + j] /= d;
+        }
+        for r in 0..n {
+            if r != col {
+                let f = a[r * w + col];
+                if f != 0. {
+                    for j in 0..w {
+                        a[r * w + j] -= f * a[col * w + j];
+                    }
+                }
+            }
+        }
+    }
+
+    let mut inv = vec![0f64; n * n];
+    for i in 0..n {
+        for j in 0..n {
+            inv[i * n + j] = a[i * w + n + j];
+        }
+    }
+    Some(inv)
+}
+
+/// Builds the inverse of a tone curve by sampling the forward curve and
+/// resampling its (assumed monotonic) response.
+fn reverse_tone_curve(curve: &ToneCurve) -> ToneCurve {
+    const N: usize = CURVE_COMPOSE_SAMPLES;
+
+    let ys: Vec<f32> = (0..N)
+        .map(|i| curve.eval_float(i as f32 / (N - 1) as f32))
+        .collect();
+
+    let increasing = ys[N - 1] >= ys[0];
+    let (lo, hi) = if increasing { (ys[0], ys[N - 1]) } else { (ys[N - 1], ys[0]) };
+
+    let mut out = vec![0f32; N];
+    for (t, slot) in out.iter_mut().enumerate() {
+        let target = t as f32 / (N - 1) as f32;
+        *slot = if target <= lo {
+            if increasing { 0. } else { 1. }
+        } else if target >= hi {
+            if increasing { 1. } else { 0. }
+        } else {
+            let mut x = 1.;
+            for i in 0..N - 1 {
+                let (y0, y1) = (ys[i], ys[i + 1]);
+                if (target >= y0 && target <= y1) || (target <= y0 && target >= y1) {
+                    let denom = y1 - y0;
+                    let frac = if denom.abs() < 1e-12 {
+                        0.
+                    } else {
+                        (target - y0) / denom
+                    };
+                    x = (i as f32 + frac) / (N - 1) as f32;
+                    break;
+                }
+            }
+            x
+        };
+    }
+
+    ToneCurve::new_tabulated(&out).unwrap()
+}
+
+/// Builds the analytic inverse of a single stage, or `None` when the stage is
+/// not invertible.
+fn reverse_stage(stage: &Stage) -> Option<Stage> {
+    // Stages defined in terms of a well-known counterpart swap directly.
+    match stage.implements {
+        StageType::Identity => return Some(Stage::new_identity(stage.input_channels)),
+        StageType::LabV2toV4 => return Some(Stage::new_labv4_to_v2()),
+        StageType::LabV4toV2 => return Some(Stage::new_labv2_to_v4()),
+        StageType::Lab2FloatPCS => return Some(Stage::new_normalize_to_lab_float()),
+        StageType::FloatPCS2Lab => return Some(Stage::new_normalize_from_lab_float()),
+        StageType::XYZ2FloatPCS => return Some(Stage::new_normalize_to_xyz_float()),
+        StageType::FloatPCS2XYZ => return Some(Stage::new_normalize_from_xyz_float()),
+        _ => {}
+    }
+
+    match stage.ty {
+        StageType::Matrix => {
+            if stage.input_channels != stage.output_channels {
+                return None;
+            }
+            let (matrix, offset) = match stage.data {
+                StageData::Matrix {
+                    ref matrix,
+                    ref offset,
+                } => (matrix, offset),
+                _ => return None,
+            };
+            let n = stage.input_channels as usize;
+            let inv = invert_matrix(matrix, n)?;
+
+            // O' = -M^-1 * O
+            let new_offset = offset.as_ref().map(|o| {
+                let mut no = vec![0f64; n];
+                for i in 0..n {
+                    let mut acc = 0.;
+                    for j in 0..n {
+                        acc += inv[i * n + j] * o[j];
+                    }
+                    no[i] = -acc;
+                }
+                no
+            });
+
+            Some(Stage::new_matrix(
+                n as u32,
+                n as u32,
+                &inv,
+                new_offset.as_deref(),
+            ))
+        }
+        StageType::CurveSet => {
+            let curves = match stage.data {
+                StageData::Curves(ref c) => c,
+                _ => return None,
+            };
+            let reversed: Vec<ToneCurve> = curves.iter().map(reverse_tone_curve).collect();
+            Some(Stage::new_tone_curves(stage.input_channels, Some(&reversed)))
+        }
+        StageType::XYZ2Lab => Some(Stage::new_lab_to_xyz()),
+        StageType::Lab2XYZ => Some(Stage::new_xyz_to_lab()),
+        StageType::Identity => Some(Stage::new_identity(stage.input_channels)),
+        // CLUTs, named-color tables, clipping and custom stages have no analytic
+        // inverse.
+        _ => None,
+    }
+}
+
 fn evaluate_xyz_to_lab(input: &[f32], output: &mut [f32], _: &Stage) {
     // From 0..1.0 to XYZ
     let xyz = CIEXYZ {
@@ -513,6 +1198,46 @@ fn evaluate_lab_to_xyz(input: &[f32], output: &mut [f32], _: &Stage) {
     output[2] = (xyz.z / MAX_ENCODEABLE_XYZ) as f32;
 }
 
+/// Maps the single input channel of a named-color stage to a list index.
+fn named_color_index(input: f32, list: &NamedColorList) -> usize {
+    if list.is_empty() {
+        return 0;
+    }
+    let last = list.len() - 1;
+    let scaled = (input.max(0.).min(1.) * last as f32).round() as usize;
+    scaled.min(last)
+}
+
+/// Outputs the stored PCS coordinates of the indexed named color, normalized to
+/// 0..1.0.
+fn evaluate_named_color_pcs(input: &[f32], output: &mut [f32], stage: &Stage) {
+    let list = match stage.data {
+        StageData::NamedColorList(ref l) => l,
+        _ => panic!("Invalid stage data (this shouldn’t happen)"),
+    };
+
+    let index = named_color_index(input[0], list);
+    let pcs = list.pcs(index);
+    for i in 0..3 {
+        output[i] = pcs[i] as f32 / 65535.;
+    }
+}
+
+/// Outputs the device colorant values of the indexed named color, normalized to
+/// 0..1.0.
+fn evaluate_named_color_device(input: &[f32], output: &mut [f32], stage: &Stage) {
+    let list = match stage.data {
+        StageData::NamedColorList(ref l) => l,
+        _ => panic!("Invalid stage data (this shouldn’t happen)"),
+    };
+
+    let index = named_color_index(input[0], list);
+    let colorants = list.device_colorants(index);
+    for i in 0..colorants.len() {
+        output[i] = colorants[i] as f32 / 65535.;
+    }
+}
+
 /// Clips values smaller than zero
 fn clipper(input: &[f32], output: &mut [f32], stage: &Stage) {
     for i in 0..stage.input_channels as usize {
@@ -522,4 +1247,92 @@ fn clipper(input: &[f32], output: &mut [f32], stage: &Stage) {
 
 fn evaluate_identity(input: &[f32], output: &mut [f32], _: &Stage) {
     copy_float_slice(input, output);
+}
+
+/// Dispatches to the user-supplied closure of a custom stage.
+fn evaluate_custom(input: &[f32], output: &mut [f32], stage: &Stage) {
+    let f = match stage.data {
+        StageData::Custom(ref f) => f,
+        _ => panic!("Invalid stage data (this shouldn’t happen)"),
+    };
+    let in_ch = stage.input_channels as usize;
+    let out_ch = stage.output_channels as usize;
+    f(&input[..in_ch], &mut output[..out_ch]);
+}
+
+/// Generic block evaluator: runs the scalar evaluator once per pixel. Used by
+/// stages that have no specialized vectorized path.
+fn eval_batch_scalar(input: &[f32], output: &mut [f32], pixel_count: usize, stage: &Stage) {
+    let in_ch = stage.input_channels as usize;
+    let out_ch = stage.output_channels as usize;
+    for p in 0..pixel_count {
+        (stage.eval_fn)(
+            &input[p * in_ch..][..in_ch],
+            &mut output[p * out_ch..][..out_ch],
+            stage,
+        );
+    }
+}
+
+fn evaluate_matrix_batch(input: &[f32], output: &mut [f32], pixel_count: usize, stage: &Stage) {
+    let (matrix, offset) = match stage.data {
+        StageData::Matrix {
+            ref matrix,
+            ref offset,
+        } => (matrix, offset),
+        _ => panic!("Invalid stage data (this shouldn’t happen)"),
+    };
+
+    let in_ch = stage.input_channels as usize;
+    let out_ch = stage.output_channels as usize;
+
+    for p in 0..pixel_count {
+        let ins = &input[p * in_ch..][..in_ch];
+        let outs = &mut output[p * out_ch..][..out_ch];
+        for i in 0..out_ch {
+            let mut tmp = 0.;
+            for j in 0..in_ch {
+                tmp += ins[j] as f64 * matrix[i * in_ch + j];
+            }
+            if let Some(offset) = offset {
+                tmp += offset[i];
+            }
+            outs[i] = tmp as f32;
+        }
+    }
+}
+
+fn evaluate_curves_batch(input: &[f32], output: &mut [f32], pixel_count: usize, stage: &Stage) {
+    let curves = match stage.data {
+        StageData::Curves(ref c) => c,
+        _ => panic!("Invalid stage data (this shouldn’t happen)"),
+    };
+
+    let ch = stage.input_channels as usize;
+    for p in 0..pixel_count {
+        let ins = &input[p * ch..][..ch];
+        let outs = &mut output[p * ch..][..ch];
+        for i in 0..curves.len() {
+            outs[i] = curves[i].eval_float(ins[i]);
+        }
+    }
+}
+
+fn clipper_batch(input: &[f32], output: &mut [f32], pixel_count: usize, stage: &Stage) {
+    let ch = stage.input_channels as usize;
+    for i in 0..pixel_count * ch {
+        output[i] = input[i].max(0.);
+    }
+}
+
+fn evaluate_xyz_to_lab_batch(input: &[f32], output: &mut [f32], pixel_count: usize, stage: &Stage) {
+    for p in 0..pixel_count {
+        evaluate_xyz_to_lab(&input[p * 3..][..3], &mut output[p * 3..][..3], stage);
+    }
+}
+
+fn evaluate_lab_to_xyz_batch(input: &[f32], output: &mut [f32], pixel_count: usize, stage: &Stage) {
+    for p in 0..pixel_count {
+        evaluate_lab_to_xyz(&input[p * 3..][..3], &mut output[p * 3..][..3], stage);
+    }
 }
\ No newline at end of file